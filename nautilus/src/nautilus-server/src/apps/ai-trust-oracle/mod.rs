@@ -6,13 +6,33 @@ use crate::common::{
 };
 use crate::{AppState, EnclaveError};
 use axum::{extract::State, Json};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL},
+    Engine as _,
+};
+use fastcrypto::ed25519::{Ed25519KeyPair, Ed25519PublicKey, Ed25519Signature};
+use fastcrypto::traits::{Signer, ToFromBytes, VerifyingKey};
+use futures_util::StreamExt;
 use reqwest::Client;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaVerifyingKey};
+use rsa::signature::Verifier as _;
+use rsa::RsaPublicKey;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
 
 const DEFAULT_WALRUS_GATEWAY: &str = "https://api.walrus.xyz";
+/// Default blob size ceiling, in bytes. `process_data` does not read it: the
+/// ceiling comes from the request or, failing that, from `AppState::max_blob_size`.
+const DEFAULT_MAX_BLOB_SIZE: u64 = 256 * 1024 * 1024;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WalrusVerificationResult {
@@ -22,6 +42,20 @@ pub struct WalrusVerificationResult {
     pub verified: bool,
     pub blob_size: u64,
     pub walrus_gateway: String,
+    /// `Some(true/false)` when a Merkle inclusion proof was supplied and checked
+    /// against `merkle_root`; `None` when the request only used the SHA256 path.
+    pub merkle_verified: Option<bool>,
+    /// `true` only when a detached signature was supplied, verified against the
+    /// claimed signer key, and that key's fingerprint is allow-listed.
+    pub signature_verified: bool,
+    /// Fingerprint (hex SHA256 of the decoded `signer_public_key` bytes: the raw
+    /// key for ed25519, the PKCS#1 DER key for RSA) of the claimed signer, set
+    /// whenever a signature was supplied regardless of whether it verified.
+    pub signer_key_id: Option<String>,
+    /// Hex SHA256 of the gateway leaf certificate's SubjectPublicKeyInfo that the
+    /// connection was pinned against. Every allow-listed gateway must have a
+    /// configured pin, so this is always present.
+    pub matched_pin_spki_sha256: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,13 +63,29 @@ pub struct WalrusVerificationRequest {
     pub blob_id: String,
     pub expected_sha256: String,
     pub walrus_gateway: Option<String>,
+    /// Hex-encoded root of the on-chain Merkle tree the blob is committed to.
+    pub merkle_root: Option<String>,
+    /// Position of the blob's leaf within the tree, used to determine sibling order.
+    pub leaf_index: Option<u64>,
+    /// Sibling hashes (hex), ordered from the leaf up to the root.
+    pub proof: Option<Vec<String>>,
+    /// Per-request override of `AppState::max_blob_size`, in bytes.
+    pub max_blob_size: Option<u64>,
+    /// Base64-encoded detached signature over the raw blob bytes.
+    pub signature: Option<String>,
+    /// `"ed25519"` or `"rsa-pkcs1-sha256"`.
+    pub signature_scheme: Option<String>,
+    /// Base64-encoded raw public key (ed25519) or PKCS#1 DER public key (RSA).
+    pub signer_public_key: Option<String>,
+    /// When `true`, additionally sign the result as a compact EdDSA JWS.
+    pub emit_jws: Option<bool>,
 }
 
 pub async fn process_data(
     State(state): State<Arc<AppState>>,
     Json(request): Json<ProcessDataRequest<WalrusVerificationRequest>>,
 ) -> Result<Json<ProcessedDataResponse<IntentMessage<WalrusVerificationResult>>>, EnclaveError> {
-    let normalized_expected = normalize_hex(&request.payload.expected_sha256)?;
+    let normalized_expected = normalize_hex("expected_sha256", &request.payload.expected_sha256)?;
     let walrus_gateway = request
         .payload
         .walrus_gateway
@@ -45,8 +95,38 @@ pub async fn process_data(
         .to_string();
     let blob_url = format!("{}/v1/blobs/{}", walrus_gateway, request.payload.blob_id);
 
+    let gateway_host = normalize_gateway_host(&walrus_gateway)?;
+    if !state.allowed_gateways.iter().any(|g| g == &gateway_host) {
+        return Err(EnclaveError::GenericError(format!(
+            "walrus_gateway host {} is not in the allow-list",
+            gateway_host
+        )));
+    }
+    let matched_pin_spki_sha256 = state
+        .pinned_cert_spki_sha256
+        .get(&gateway_host)
+        .cloned()
+        .ok_or_else(|| {
+            EnclaveError::GenericError(format!(
+                "no certificate pin configured for allow-listed gateway host {}",
+                gateway_host
+            ))
+        })?;
+
+    let verifier = Arc::new(PinnedSpkiVerifier::new(state.pinned_cert_spki_sha256.clone())?);
+    let tls_config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth();
+
     let client = Client::builder()
+        .use_preconfigured_tls(tls_config)
         .timeout(std::time::Duration::from_secs(15))
+        // The allow-list and certificate pin are only checked against this
+        // initial host; a followed redirect would reach a different host over
+        // a connection neither control applies to. Disable redirects instead of
+        // re-validating each hop.
+        .redirect(reqwest::redirect::Policy::none())
         .build()
         .map_err(|e| EnclaveError::GenericError(format!("Failed to init HTTP client: {}", e)))?;
 
@@ -66,72 +146,450 @@ pub async fn process_data(
         )));
     }
 
-    let blob_bytes = response.bytes().await.map_err(|e| {
-        EnclaveError::GenericError(format!(
-            "Failed to read Walrus blob {}: {}",
-            request.payload.blob_id, e
-        ))
-    })?;
+    let max_blob_size = request
+        .payload
+        .max_blob_size
+        .unwrap_or(state.max_blob_size);
+
+    if let Some(content_length) = response.content_length() {
+        if content_length > max_blob_size {
+            return Err(EnclaveError::GenericError(format!(
+                "Walrus blob {} advertises {} bytes, exceeding the {}-byte ceiling",
+                request.payload.blob_id, content_length, max_blob_size
+            )));
+        }
+    }
+
+    // Signature verification needs the raw bytes, not just the digest, so retain a
+    // buffer (bounded by `max_blob_size`, same as the streaming ceiling) only when
+    // a signature was actually supplied.
+    let needs_blob_buffer = request.payload.signature.is_some();
+    let mut blob_buffer = needs_blob_buffer.then(Vec::new);
 
     let mut hasher = Sha256::new();
-    hasher.update(&blob_bytes);
+    let mut blob_size: u64 = 0;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            EnclaveError::GenericError(format!(
+                "Failed to read Walrus blob {}: {}",
+                request.payload.blob_id, e
+            ))
+        })?;
+        blob_size += chunk.len() as u64;
+        if blob_size > max_blob_size {
+            return Err(EnclaveError::GenericError(format!(
+                "Walrus blob {} exceeded the {}-byte ceiling",
+                request.payload.blob_id, max_blob_size
+            )));
+        }
+        hasher.update(&chunk);
+        if let Some(buffer) = blob_buffer.as_mut() {
+            buffer.extend_from_slice(&chunk);
+        }
+    }
+
     let digest = hasher.finalize();
     let computed_sha256 = format!("{:x}", digest);
     let verified = computed_sha256.eq_ignore_ascii_case(&normalized_expected);
 
+    let (signature_verified, signer_key_id) = match (
+        &request.payload.signature,
+        &request.payload.signature_scheme,
+        &request.payload.signer_public_key,
+    ) {
+        (Some(signature), Some(scheme), Some(signer_public_key)) => {
+            let (verified, key_id) = verify_content_signature(
+                scheme,
+                signature,
+                signer_public_key,
+                blob_buffer.as_deref().unwrap_or(&[]),
+                &state.allowed_signer_keys,
+            )?;
+            (verified, Some(key_id))
+        }
+        (None, None, None) => (false, None),
+        _ => {
+            return Err(EnclaveError::GenericError(
+                "signature, signature_scheme, and signer_public_key must be supplied together"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let merkle_verified = match (
+        &request.payload.merkle_root,
+        request.payload.leaf_index,
+        &request.payload.proof,
+    ) {
+        (Some(root), Some(leaf_index), Some(proof)) => Some(verify_merkle_proof(
+            root,
+            leaf_index,
+            proof,
+            digest.as_slice(),
+        )?),
+        (None, None, None) => None,
+        _ => {
+            return Err(EnclaveError::GenericError(
+                "merkle_root, leaf_index, and proof must be supplied together".to_string(),
+            ))
+        }
+    };
+
     let timestamp_ms = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| EnclaveError::GenericError(format!("Invalid system time: {}", e)))?
         .as_millis() as u64;
 
-    Ok(Json(to_signed_response(
-        &state.eph_kp,
-        WalrusVerificationResult {
-            blob_id: request.payload.blob_id,
-            expected_sha256: normalized_expected,
-            computed_sha256,
-            verified,
-            blob_size: blob_bytes.len() as u64,
-            walrus_gateway,
-        },
-        timestamp_ms,
-        IntentScope::ProcessData,
-    )))
+    let result = WalrusVerificationResult {
+        blob_id: request.payload.blob_id,
+        expected_sha256: normalized_expected,
+        computed_sha256,
+        verified,
+        blob_size,
+        walrus_gateway,
+        merkle_verified,
+        signature_verified,
+        signer_key_id,
+        matched_pin_spki_sha256,
+    };
+
+    let jws = request
+        .payload
+        .emit_jws
+        .unwrap_or(false)
+        .then(|| build_jws(&state.eph_kp, &result, timestamp_ms))
+        .transpose()?;
+
+    let signed = to_signed_response(&state.eph_kp, result, timestamp_ms, IntentScope::ProcessData);
+    Ok(Json(ProcessedDataResponse { jws, ..signed }))
 }
 
-fn normalize_hex(input: &str) -> Result<String, EnclaveError> {
+/// Serializes `result` as a compact EdDSA JWS (RFC 7515) so off-chain consumers
+/// can verify the attestation with standard JWT tooling instead of parsing the
+/// Sui-specific `IntentMessage` envelope.
+fn build_jws(
+    eph_kp: &Ed25519KeyPair,
+    result: &WalrusVerificationResult,
+    timestamp_ms: u64,
+) -> Result<String, EnclaveError> {
+    let header_b64 = BASE64_URL.encode(br#"{"alg":"EdDSA","typ":"JWT"}"#);
+
+    let mut payload = serde_json::to_value(result).map_err(|e| {
+        EnclaveError::GenericError(format!("failed to serialize JWS payload: {}", e))
+    })?;
+    let payload_map = payload.as_object_mut().ok_or_else(|| {
+        EnclaveError::GenericError("JWS payload must serialize to a JSON object".to_string())
+    })?;
+    payload_map.insert("iat".to_string(), serde_json::json!(timestamp_ms));
+    payload_map.insert(
+        "scope".to_string(),
+        serde_json::to_value(IntentScope::ProcessData).map_err(|e| {
+            EnclaveError::GenericError(format!("failed to serialize scope claim: {}", e))
+        })?,
+    );
+
+    let payload_b64 = BASE64_URL.encode(serde_json::to_vec(&payload).map_err(|e| {
+        EnclaveError::GenericError(format!("failed to serialize JWS payload: {}", e))
+    })?);
+
+    let signing_input = format!("{}.{}", header_b64, payload_b64);
+    let signature = eph_kp.sign(signing_input.as_bytes());
+    let signature_b64 = BASE64_URL.encode(signature.as_bytes());
+
+    Ok(format!("{}.{}", signing_input, signature_b64))
+}
+
+fn normalize_hex(field_name: &str, input: &str) -> Result<String, EnclaveError> {
     let trimmed = input.trim().trim_start_matches("0x");
     if trimmed.is_empty() {
-        return Err(EnclaveError::GenericError(
-            "expected_sha256 is required".to_string(),
-        ));
+        return Err(EnclaveError::GenericError(format!(
+            "{} is required",
+            field_name
+        )));
     }
     if trimmed.len() % 2 != 0 || !trimmed.chars().all(|c| c.is_ascii_hexdigit()) {
         return Err(EnclaveError::GenericError(format!(
-            "expected_sha256 must be valid hex, got {}",
-            input
+            "{} must be valid hex, got {}",
+            field_name, input
         )));
     }
     Ok(trimmed.to_ascii_lowercase())
 }
 
+fn normalize_gateway_host(gateway: &str) -> Result<String, EnclaveError> {
+    let parsed = reqwest::Url::parse(gateway)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid walrus_gateway URL: {}", e)))?;
+    if parsed.scheme() != "https" {
+        return Err(EnclaveError::GenericError(format!(
+            "walrus_gateway must use https, got scheme {}",
+            parsed.scheme()
+        )));
+    }
+    parsed
+        .host_str()
+        .map(|host| host.to_ascii_lowercase())
+        .ok_or_else(|| EnclaveError::GenericError("walrus_gateway URL has no host".to_string()))
+}
+
+fn spki_sha256_hex(cert: &CertificateDer<'_>) -> Result<String, EnclaveError> {
+    let (_, parsed) = X509Certificate::from_der(cert.as_ref())
+        .map_err(|e| EnclaveError::GenericError(format!("invalid gateway certificate: {}", e)))?;
+    let spki_der = parsed.public_key().raw;
+    Ok(bytes_to_hex(Sha256::digest(spki_der).as_slice()))
+}
+
+fn server_name_to_host(server_name: &ServerName<'_>) -> String {
+    match server_name {
+        ServerName::DnsName(name) => name.as_ref().to_ascii_lowercase(),
+        ServerName::IpAddress(ip) => ip.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// A `rustls` certificate verifier that runs normal WebPKI chain validation and
+/// then additionally requires the leaf certificate's SubjectPublicKeyInfo to match
+/// a fingerprint pinned per-host, closing the MITM hole a compromised or
+/// mis-issued gateway certificate would otherwise open.
+#[derive(Debug)]
+struct PinnedSpkiVerifier {
+    inner: Arc<rustls::client::WebPkiServerVerifier>,
+    pinned_cert_spki_sha256: HashMap<String, String>,
+}
+
+impl PinnedSpkiVerifier {
+    fn new(pinned_cert_spki_sha256: HashMap<String, String>) -> Result<Self, EnclaveError> {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let inner = rustls::client::WebPkiServerVerifier::builder(Arc::new(roots))
+            .build()
+            .map_err(|e| {
+                EnclaveError::GenericError(format!("failed to build certificate verifier: {}", e))
+            })?;
+        Ok(Self {
+            inner,
+            pinned_cert_spki_sha256,
+        })
+    }
+}
+
+impl ServerCertVerifier for PinnedSpkiVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+
+        // Every allow-listed host must have a configured pin — an allow-listed
+        // host with no pin would get only stock WebPKI, i.e. no protection
+        // beyond trusting a public CA, which defeats the point of pinning. Fail
+        // closed rather than silently falling back to unpinned trust.
+        let host = server_name_to_host(server_name);
+        let pinned = self.pinned_cert_spki_sha256.get(&host).ok_or_else(|| {
+            rustls::Error::General(format!("no certificate pin configured for host {}", host))
+        })?;
+
+        let spki_sha256 = spki_sha256_hex(end_entity)
+            .map_err(|e| rustls::Error::General(e.to_string()))?;
+        if !constant_time_eq(spki_sha256.as_bytes(), pinned.as_bytes()) {
+            return Err(rustls::Error::General(format!(
+                "certificate pin mismatch for host {}",
+                host
+            )));
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Folds a Merkle inclusion proof from `leaf` up to the root, taking the sibling
+/// order at each depth from bit `d` of `leaf_index` (0 = leaf is the left child).
+fn verify_merkle_proof(
+    expected_root: &str,
+    leaf_index: u64,
+    proof: &[String],
+    leaf: &[u8],
+) -> Result<bool, EnclaveError> {
+    let expected_root = normalize_hex("merkle_root", expected_root)?;
+    let depth = proof.len();
+    if depth >= u64::BITS as usize || leaf_index >= (1u64 << depth) {
+        return Err(EnclaveError::GenericError(format!(
+            "leaf_index {} is out of range for a proof of depth {}",
+            leaf_index, depth
+        )));
+    }
+
+    let mut node = leaf.to_vec();
+    for (d, sibling_hex) in proof.iter().enumerate() {
+        let sibling = hex_to_bytes("proof", sibling_hex)?;
+        let mut hasher = Sha256::new();
+        if (leaf_index >> d) & 1 == 0 {
+            hasher.update(&node);
+            hasher.update(&sibling);
+        } else {
+            hasher.update(&sibling);
+            hasher.update(&node);
+        }
+        node = hasher.finalize().to_vec();
+    }
+
+    let computed_root = bytes_to_hex(&node);
+    Ok(constant_time_eq(computed_root.as_bytes(), expected_root.as_bytes()))
+}
+
+/// Verifies a detached signature over `blob_bytes` and checks the signer's key
+/// fingerprint against `allowed_signer_keys`. Returns `(verified, key_id)`; `key_id`
+/// is populated even when the key is not allow-listed so callers can see who signed.
+fn verify_content_signature(
+    scheme: &str,
+    signature_b64: &str,
+    signer_public_key_b64: &str,
+    blob_bytes: &[u8],
+    allowed_signer_keys: &HashSet<String>,
+) -> Result<(bool, String), EnclaveError> {
+    match scheme {
+        "ed25519" => verify_ed25519_signature(
+            signature_b64,
+            signer_public_key_b64,
+            blob_bytes,
+            allowed_signer_keys,
+        ),
+        "rsa-pkcs1-sha256" => verify_rsa_pkcs1_signature(
+            signature_b64,
+            signer_public_key_b64,
+            blob_bytes,
+            allowed_signer_keys,
+        ),
+        other => Err(EnclaveError::GenericError(format!(
+            "unsupported signature_scheme: {}",
+            other
+        ))),
+    }
+}
+
+fn verify_ed25519_signature(
+    signature_b64: &str,
+    signer_public_key_b64: &str,
+    blob_bytes: &[u8],
+    allowed_signer_keys: &HashSet<String>,
+) -> Result<(bool, String), EnclaveError> {
+    let public_key_bytes = BASE64
+        .decode(signer_public_key_b64)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid signer_public_key: {}", e)))?;
+    let public_key = Ed25519PublicKey::from_bytes(&public_key_bytes)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid ed25519 public key: {}", e)))?;
+    let signature_bytes = BASE64
+        .decode(signature_b64)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid signature: {}", e)))?;
+    let signature = Ed25519Signature::from_bytes(&signature_bytes)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid ed25519 signature: {}", e)))?;
+
+    let key_id = bytes_to_hex(Sha256::digest(&public_key_bytes).as_slice());
+    let signature_valid = public_key.verify(blob_bytes, &signature).is_ok();
+    Ok((signature_valid && allowed_signer_keys.contains(&key_id), key_id))
+}
+
+fn verify_rsa_pkcs1_signature(
+    signature_b64: &str,
+    signer_public_key_der_b64: &str,
+    blob_bytes: &[u8],
+    allowed_signer_keys: &HashSet<String>,
+) -> Result<(bool, String), EnclaveError> {
+    let public_key_der = BASE64
+        .decode(signer_public_key_der_b64)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid signer_public_key: {}", e)))?;
+    let public_key = RsaPublicKey::from_pkcs1_der(&public_key_der)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid RSA public key: {}", e)))?;
+    let signature_bytes = BASE64
+        .decode(signature_b64)
+        .map_err(|e| EnclaveError::GenericError(format!("invalid signature: {}", e)))?;
+    let signature = RsaSignature::try_from(signature_bytes.as_slice())
+        .map_err(|e| EnclaveError::GenericError(format!("invalid RSA signature: {}", e)))?;
+
+    let key_id = bytes_to_hex(Sha256::digest(&public_key_der).as_slice());
+    let verifying_key = RsaVerifyingKey::<Sha256>::new(public_key);
+    let signature_valid = verifying_key.verify(blob_bytes, &signature).is_ok();
+    Ok((signature_valid && allowed_signer_keys.contains(&key_id), key_id))
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_to_bytes(field_name: &str, input: &str) -> Result<Vec<u8>, EnclaveError> {
+    let normalized = normalize_hex(field_name, input)?;
+    (0..normalized.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&normalized[i..i + 2], 16)
+                .map_err(|e| EnclaveError::GenericError(format!("invalid hex byte: {}", e)))
+        })
+        .collect()
+}
+
+/// Constant-time byte comparison so a mismatched Merkle root doesn't leak timing
+/// information about how many leading bytes matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::common::IntentMessage;
     use axum::{extract::State, Json};
     use fastcrypto::{ed25519::Ed25519KeyPair, traits::KeyPair};
+    use rsa::RsaPrivateKey;
 
     #[tokio::test]
     async fn normalize_rejects_invalid_hex() {
-        assert!(normalize_hex("zz").is_err());
-        assert!(normalize_hex("").is_err());
-        assert!(normalize_hex("0x1").is_err());
+        assert!(normalize_hex("expected_sha256", "zz").is_err());
+        assert!(normalize_hex("expected_sha256", "").is_err());
+        assert!(normalize_hex("expected_sha256", "0x1").is_err());
     }
 
     #[tokio::test]
     async fn normalize_accepts_prefixed() {
-        assert_eq!(normalize_hex("0xABCD").unwrap(), "abcd".to_string());
+        assert_eq!(
+            normalize_hex("expected_sha256", "0xABCD").unwrap(),
+            "abcd".to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn normalize_rejects_invalid_hex_includes_field_name() {
+        let err = normalize_hex("merkle_root", "zz").unwrap_err();
+        assert!(format!("{}", err).contains("merkle_root"));
     }
 
     #[tokio::test]
@@ -139,6 +597,10 @@ mod tests {
         let state = Arc::new(AppState {
             eph_kp: Ed25519KeyPair::generate(&mut rand::thread_rng()),
             api_key: String::new(),
+            max_blob_size: DEFAULT_MAX_BLOB_SIZE,
+            allowed_signer_keys: HashSet::new(),
+            allowed_gateways: vec![],
+            pinned_cert_spki_sha256: HashMap::new(),
         });
 
         let payload = WalrusVerificationResult {
@@ -148,6 +610,10 @@ mod tests {
             verified: true,
             blob_size: 0,
             walrus_gateway: DEFAULT_WALRUS_GATEWAY.to_string(),
+            merkle_verified: None,
+            signature_verified: false,
+            signer_key_id: None,
+            matched_pin_spki_sha256: "deadbeef".to_string(),
         };
 
         let signed =
@@ -155,4 +621,199 @@ mod tests {
         assert_eq!(signed.response.data.blob_id, payload.blob_id);
         assert!(!signed.signature.is_empty());
     }
+
+    #[tokio::test]
+    async fn merkle_proof_verifies_two_leaf_tree() {
+        let leaf0 = Sha256::digest(b"leaf-0");
+        let leaf1 = Sha256::digest(b"leaf-1");
+        let mut hasher = Sha256::new();
+        hasher.update(leaf0);
+        hasher.update(leaf1);
+        let root = bytes_to_hex(&hasher.finalize());
+
+        let proof = vec![bytes_to_hex(&leaf1)];
+        assert!(verify_merkle_proof(&root, 0, &proof, &leaf0).unwrap());
+
+        let proof = vec![bytes_to_hex(&leaf0)];
+        assert!(verify_merkle_proof(&root, 1, &proof, &leaf1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn merkle_proof_rejects_wrong_root() {
+        let leaf0 = Sha256::digest(b"leaf-0");
+        let leaf1 = Sha256::digest(b"leaf-1");
+        let proof = vec![bytes_to_hex(&leaf1)];
+        let bogus_root = bytes_to_hex(&Sha256::digest(b"not-the-root"));
+        assert!(!verify_merkle_proof(&bogus_root, 0, &proof, &leaf0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn merkle_proof_rejects_out_of_range_leaf_index() {
+        let leaf0 = Sha256::digest(b"leaf-0");
+        let proof = vec![bytes_to_hex(&leaf0)];
+        assert!(verify_merkle_proof("00", 2, &proof, &leaf0).is_err());
+    }
+
+    #[tokio::test]
+    async fn ed25519_signature_requires_allow_listed_key() {
+        let keypair = Ed25519KeyPair::generate(&mut rand::thread_rng());
+        let blob = b"signed blob contents";
+        let signature = keypair.sign(blob);
+        let public_key_b64 = BASE64.encode(keypair.public().as_bytes());
+        let signature_b64 = BASE64.encode(signature.as_bytes());
+        let key_id = bytes_to_hex(Sha256::digest(keypair.public().as_bytes()).as_slice());
+
+        let (verified, returned_key_id) = verify_content_signature(
+            "ed25519",
+            &signature_b64,
+            &public_key_b64,
+            blob,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(!verified);
+        assert_eq!(returned_key_id, key_id);
+
+        let mut allowed = HashSet::new();
+        allowed.insert(key_id.clone());
+        let (verified, _) = verify_content_signature(
+            "ed25519",
+            &signature_b64,
+            &public_key_b64,
+            blob,
+            &allowed,
+        )
+        .unwrap();
+        assert!(verified);
+    }
+
+    #[tokio::test]
+    async fn ed25519_signature_rejects_tampered_blob() {
+        let keypair = Ed25519KeyPair::generate(&mut rand::thread_rng());
+        let signature = keypair.sign(b"original");
+        let public_key_b64 = BASE64.encode(keypair.public().as_bytes());
+        let signature_b64 = BASE64.encode(signature.as_bytes());
+        let key_id = bytes_to_hex(Sha256::digest(keypair.public().as_bytes()).as_slice());
+        let mut allowed = HashSet::new();
+        allowed.insert(key_id);
+
+        let (verified, _) = verify_content_signature(
+            "ed25519",
+            &signature_b64,
+            &public_key_b64,
+            b"tampered",
+            &allowed,
+        )
+        .unwrap();
+        assert!(!verified);
+    }
+
+    fn rsa_test_keypair() -> (rsa::pkcs1v15::SigningKey<Sha256>, String) {
+        use rsa::pkcs1::EncodeRsaPublicKey;
+
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let public_key_der = RsaPublicKey::from(&private_key)
+            .to_pkcs1_der()
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        let public_key_b64 = BASE64.encode(&public_key_der);
+        (rsa::pkcs1v15::SigningKey::<Sha256>::new(private_key), public_key_b64)
+    }
+
+    #[tokio::test]
+    async fn rsa_signature_requires_allow_listed_key() {
+        use rsa::signature::{RandomizedSigner, SignatureEncoding};
+
+        let (signing_key, public_key_b64) = rsa_test_keypair();
+        let public_key_der = BASE64.decode(&public_key_b64).unwrap();
+        let blob = b"rsa signed blob contents";
+        let signature = signing_key.sign_with_rng(&mut rand::thread_rng(), blob);
+        let signature_b64 = BASE64.encode(signature.to_bytes());
+        let key_id = bytes_to_hex(Sha256::digest(&public_key_der).as_slice());
+
+        let (verified, returned_key_id) = verify_content_signature(
+            "rsa-pkcs1-sha256",
+            &signature_b64,
+            &public_key_b64,
+            blob,
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert!(!verified);
+        assert_eq!(returned_key_id, key_id);
+
+        let mut allowed = HashSet::new();
+        allowed.insert(key_id);
+        let (verified, _) = verify_content_signature(
+            "rsa-pkcs1-sha256",
+            &signature_b64,
+            &public_key_b64,
+            blob,
+            &allowed,
+        )
+        .unwrap();
+        assert!(verified);
+    }
+
+    #[tokio::test]
+    async fn rsa_signature_rejects_tampered_blob() {
+        use rsa::signature::{RandomizedSigner, SignatureEncoding};
+
+        let (signing_key, public_key_b64) = rsa_test_keypair();
+        let public_key_der = BASE64.decode(&public_key_b64).unwrap();
+        let signature = signing_key.sign_with_rng(&mut rand::thread_rng(), b"original");
+        let signature_b64 = BASE64.encode(signature.to_bytes());
+        let key_id = bytes_to_hex(Sha256::digest(&public_key_der).as_slice());
+        let mut allowed = HashSet::new();
+        allowed.insert(key_id);
+
+        let (verified, _) = verify_content_signature(
+            "rsa-pkcs1-sha256",
+            &signature_b64,
+            &public_key_b64,
+            b"tampered",
+            &allowed,
+        )
+        .unwrap();
+        assert!(!verified);
+    }
+
+    #[tokio::test]
+    async fn jws_has_three_segments_with_eddsa_header() {
+        let keypair = Ed25519KeyPair::generate(&mut rand::thread_rng());
+        let result = WalrusVerificationResult {
+            blob_id: "blob".to_string(),
+            expected_sha256: "deadbeef".to_string(),
+            computed_sha256: "deadbeef".to_string(),
+            verified: true,
+            blob_size: 0,
+            walrus_gateway: DEFAULT_WALRUS_GATEWAY.to_string(),
+            merkle_verified: None,
+            signature_verified: false,
+            signer_key_id: None,
+            matched_pin_spki_sha256: "deadbeef".to_string(),
+        };
+
+        let jws = build_jws(&keypair, &result, 1_700_000_000_000).unwrap();
+        let segments: Vec<&str> = jws.split('.').collect();
+        assert_eq!(segments.len(), 3);
+
+        let header_bytes = BASE64_URL.decode(segments[0]).unwrap();
+        assert_eq!(header_bytes, br#"{"alg":"EdDSA","typ":"JWT"}"#);
+
+        let payload_bytes = BASE64_URL.decode(segments[1]).unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&payload_bytes).unwrap();
+        assert_eq!(payload["blob_id"], "blob");
+        assert_eq!(payload["iat"], 1_700_000_000_000u64);
+    }
+
+    #[test]
+    fn normalize_gateway_host_lowercases_and_strips_path() {
+        assert_eq!(
+            normalize_gateway_host("https://API.Walrus.xyz/v1").unwrap(),
+            "api.walrus.xyz"
+        );
+        assert!(normalize_gateway_host("not a url").is_err());
+    }
 }